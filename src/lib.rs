@@ -1,4 +1,10 @@
-pub trait GameState<'a>: 'a + Clone + Ord {
+use rayon::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub mod mcts;
+
+pub trait GameState<'a>: 'a + Clone + Ord + std::hash::Hash {
     type It: IntoIterator<Item = Self>;
 
     // computes if the game is ended in favor of player
@@ -51,7 +57,7 @@ pub trait GameState<'a>: 'a + Clone + Ord {
         depth: i32,
         mut alpha: i32,
         mut beta: i32,
-        table: &mut Table<Self>,
+        table: &Table<Self>,
     ) -> i32 {
         if depth == 0 || self.win(-player) {
             return player * self.value() * (depth + 1);
@@ -68,13 +74,91 @@ pub trait GameState<'a>: 'a + Clone + Ord {
         let orig_alpha = alpha;
         let orig_beta = beta;
 
+        // try the move remembered from the previous (shallower) iteration first so the
+        // principal variation is searched before its siblings and `alpha` tightens early
+        let children = self.ordered_children(player, depth, table);
+
         let mut best_value = -std::i32::MAX;
+        let mut best_child = None;
 
-        for state in self.possibilities(player) {
+        for state in children {
             let value = -state.negamax_table(-player, depth - 1, -beta, -alpha, table);
 
             if value > best_value {
                 best_value = value;
+                best_child = Some(state.clone());
+            }
+            if value > alpha {
+                alpha = value;
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        table.insert(
+            self.clone(),
+            player,
+            depth,
+            orig_alpha,
+            orig_beta,
+            best_value,
+            best_child,
+        );
+        best_value
+    }
+
+    // Principal Variation Search variant of `negamax_table`: the eldest child is
+    // searched with the full `[-beta, -alpha]` window, every younger sibling is first
+    // probed with a null window `[-alpha-1, -alpha]` and only re-searched with the full
+    // window when the probe lands strictly inside `(alpha, beta)`. Bounds, cutoff and
+    // the transposition `insert` classification are exactly as in `negamax_table`; only
+    // the window handed to the children changes.
+    fn negascout(
+        &self,
+        player: i32,
+        depth: i32,
+        mut alpha: i32,
+        mut beta: i32,
+        table: &Table<Self>,
+    ) -> i32 {
+        if depth == 0 || self.win(-player) {
+            return player * self.value() * (depth + 1);
+        }
+
+        if depth <= 2 {
+            return self.negamax(player, depth, alpha, beta);
+        }
+
+        if let Some(s) = table.get(self, player, depth, &mut alpha, &mut beta) {
+            return s;
+        }
+
+        let orig_alpha = alpha;
+        let orig_beta = beta;
+
+        let children = self.ordered_children(player, depth, table);
+
+        let mut best_value = -std::i32::MAX;
+        let mut best_child = None;
+        let mut first = true;
+
+        for state in children {
+            let value = if first {
+                first = false;
+                -state.negascout(-player, depth - 1, -beta, -alpha, table)
+            } else {
+                let probe = -state.negascout(-player, depth - 1, -alpha - 1, -alpha, table);
+                if alpha < probe && probe < beta {
+                    -state.negascout(-player, depth - 1, -beta, -alpha, table)
+                } else {
+                    probe
+                }
+            };
+
+            if value > best_value {
+                best_value = value;
+                best_child = Some(state.clone());
             }
             if value > alpha {
                 alpha = value;
@@ -91,17 +175,122 @@ pub trait GameState<'a>: 'a + Clone + Ord {
             orig_alpha,
             orig_beta,
             best_value,
+            best_child,
+        );
+        best_value
+    }
+
+    // parallel counterpart of `negamax_table` using a "Young Brothers Wait" scheme:
+    // the eldest child is searched alone to tighten `alpha`, then the remaining
+    // siblings are fanned out across the rayon pool. Speculative exploration weakens
+    // the alpha-beta cutoffs, so the leaf-near shortcut (`depth <= 2`) stays sequential
+    // to keep the spawning overhead from dominating.
+    fn negamax_parallel(
+        &self,
+        player: i32,
+        depth: i32,
+        mut alpha: i32,
+        mut beta: i32,
+        table: &Table<Self>,
+    ) -> i32
+    where
+        Self: Send + Sync,
+    {
+        if depth == 0 || self.win(-player) {
+            return player * self.value() * (depth + 1);
+        }
+
+        if depth <= 2 {
+            return self.negamax(player, depth, alpha, beta);
+        }
+
+        if let Some(s) = table.get(self, player, depth, &mut alpha, &mut beta) {
+            return s;
+        }
+
+        let orig_alpha = alpha;
+        let orig_beta = beta;
+
+        let children = self.ordered_children(player, depth, table);
+
+        let mut best_value = -std::i32::MAX;
+        let mut best_child = None;
+
+        // search the eldest child sequentially to establish a tightened bound
+        if let Some(first) = children.first() {
+            let value = -first.negamax_parallel(-player, depth - 1, -beta, -alpha, table);
+            best_value = value;
+            best_child = Some(first.clone());
+            if value > alpha {
+                alpha = value;
+            }
+        }
+
+        // explore the younger brothers in parallel against that bound
+        if alpha < beta && children.len() > 1 {
+            let rest = &children[1..];
+            let values: Vec<i32> = rest
+                .par_iter()
+                .map(|state| -state.negamax_parallel(-player, depth - 1, -beta, -alpha, table))
+                .collect();
+            for (state, value) in rest.iter().zip(values) {
+                if value > best_value {
+                    best_value = value;
+                    best_child = Some(state.clone());
+                }
+            }
+        }
+
+        table.insert(
+            self.clone(),
+            player,
+            depth,
+            orig_alpha,
+            orig_beta,
+            best_value,
+            best_child,
         );
         best_value
     }
 
+    // collect the children of `self` and, if the table remembers a best move from the
+    // previous iteration at `depth - 1`, bring it to the front of the list.
+    //
+    // Note: the `depth <= 2` shortcut returns before any `insert`, so nothing is ever
+    // stored at those levels. The `depth - 1` lookup therefore always misses at
+    // `depth == 3`; move ordering only starts paying off from `depth == 4` upward.
+    fn ordered_children(&self, player: i32, depth: i32, table: &Table<Self>) -> Vec<Self> {
+        let mut children: Vec<Self> = self.possibilities(player).into_iter().collect();
+
+        if let Some(best) = table.best_move(self, player, depth - 1) {
+            if let Some(pos) = children
+                .iter()
+                .position(|child| child_key(child, player) == best)
+            {
+                children.swap(0, pos);
+            }
+        }
+
+        children
+    }
+
     // compute the value in player +1 perspective
     // turn of `player` to play
-    fn negamax_value(&self, player: i32, depth: i32, table: &mut Table<Self>) -> i32 {
+    fn negamax_value(&self, player: i32, depth: i32, table: &Table<Self>) -> i32 {
         player * self.negamax_table(player, depth, -std::i32::MAX, std::i32::MAX, table)
     }
 
-    fn bot_play(&self, player: i32, depth: i32, table: &mut Table<Self>) -> Vec<Self> {
+    // iterative deepening: search depth 1, 2, ... `depth`, reusing the table so each
+    // shallow iteration leaves behind the best moves that order the next, deeper one.
+    fn negamax_iterative(&self, player: i32, depth: i32, table: &Table<Self>) -> i32 {
+        let mut value = 0;
+        for d in 1..=depth {
+            value = self.negamax_value(player, d, table);
+        }
+        value
+    }
+
+    fn bot_play(&self, player: i32, depth: i32, table: &Table<Self>) -> Vec<Self> {
         let mut best_value = -std::i32::MAX;
         let mut results = Vec::new();
 
@@ -119,6 +308,224 @@ pub trait GameState<'a>: 'a + Clone + Ord {
 
         results
     }
+
+    // iterative-deepening driver for `bot_play`: the shallow iterations warm the table
+    // with best moves before the final depth decides the returned moves.
+    fn bot_play_iterative(&self, player: i32, depth: i32, table: &Table<Self>) -> Vec<Self> {
+        for d in 1..=depth {
+            self.negamax_value(player, d, table);
+        }
+        self.bot_play(player, depth, table)
+    }
+
+    // deadline-aware counterpart of `negamax_table`: it checks the clock on entry and
+    // returns `None` the moment the budget is spent. Because a node only writes to the
+    // table once its whole child loop has completed, an abort propagates upward through
+    // `?` before any `insert`, so the transposition table is never left half-updated.
+    fn negamax_deadline(
+        &self,
+        player: i32,
+        depth: i32,
+        mut alpha: i32,
+        mut beta: i32,
+        table: &Table<Self>,
+        deadline: Instant,
+    ) -> Option<i32> {
+        if Instant::now() >= deadline {
+            return None;
+        }
+
+        if depth == 0 || self.win(-player) {
+            return Some(player * self.value() * (depth + 1));
+        }
+
+        if depth <= 2 {
+            return Some(self.negamax(player, depth, alpha, beta));
+        }
+
+        if let Some(s) = table.get(self, player, depth, &mut alpha, &mut beta) {
+            return Some(s);
+        }
+
+        let orig_alpha = alpha;
+        let orig_beta = beta;
+
+        let children = self.ordered_children(player, depth, table);
+
+        let mut best_value = -std::i32::MAX;
+        let mut best_child = None;
+
+        for state in children {
+            let value = -state.negamax_deadline(-player, depth - 1, -beta, -alpha, table, deadline)?;
+
+            if value > best_value {
+                best_value = value;
+                best_child = Some(state.clone());
+            }
+            if value > alpha {
+                alpha = value;
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        table.insert(
+            self.clone(),
+            player,
+            depth,
+            orig_alpha,
+            orig_beta,
+            best_value,
+            best_child,
+        );
+        Some(best_value)
+    }
+
+    // one `bot_play` pass at a fixed `depth` that aborts on the deadline, yielding `None`
+    // for a half-finished iteration.
+    fn bot_play_deadline(
+        &self,
+        player: i32,
+        depth: i32,
+        table: &Table<Self>,
+        deadline: Instant,
+    ) -> Option<Vec<Self>> {
+        let mut best_value = -std::i32::MAX;
+        let mut results = Vec::new();
+
+        for state in self.possibilities(player) {
+            let value =
+                -state.negamax_deadline(-player, depth, -std::i32::MAX, std::i32::MAX, table, deadline)?;
+
+            if value > best_value {
+                best_value = value;
+                results.clear();
+            }
+            if value == best_value {
+                results.push(state);
+            }
+        }
+
+        Some(results)
+    }
+
+    // iterative deepening against a wall-clock budget. It returns the best moves from the
+    // deepest iteration that *completed* (never a half-searched one) together with the
+    // depth actually reached, so callers can gauge how much to trust the answer.
+    //
+    // Every `d + 1` pass is a genuinely deeper search that can still revise the answer, so
+    // the loop keeps deepening until the deadline; a stable best-move set across one ply is
+    // not proof of convergence and must not cut the search short.
+    fn bot_play_timed(
+        &self,
+        player: i32,
+        time_limit: Duration,
+        table: &Table<Self>,
+    ) -> (Vec<Self>, i32) {
+        let deadline = Instant::now() + time_limit;
+
+        // depth-0 fallback: if not even the first iteration finishes, return the legal
+        // moves unranked rather than nothing.
+        let mut best: Vec<Self> = self.possibilities(player).into_iter().collect();
+        let mut reached = 0;
+
+        let mut depth = 1;
+        while Instant::now() < deadline {
+            match self.bot_play_deadline(player, depth, table, deadline) {
+                Some(moves) => {
+                    best = moves;
+                    reached = depth;
+                    depth += 1;
+                }
+                None => break,
+            }
+        }
+
+        (best, reached)
+    }
+
+    // parallel counterpart of `bot_play`: the root children carry independent full
+    // windows (exactly as the sequential version), so they are simply mapped over the
+    // pool; the Young Brothers Wait tightening happens one ply down in `negamax_parallel`.
+    fn bot_play_parallel(&self, player: i32, depth: i32, table: &Table<Self>) -> Vec<Self>
+    where
+        Self: Send + Sync,
+    {
+        let children: Vec<Self> = self.possibilities(player).into_iter().collect();
+
+        let values: Vec<i32> = children
+            .par_iter()
+            .map(|state| {
+                -state.negamax_parallel(-player, depth, -std::i32::MAX, std::i32::MAX, table)
+            })
+            .collect();
+
+        let best_value = values.iter().cloned().max().unwrap_or(-std::i32::MAX);
+
+        children
+            .into_iter()
+            .zip(values)
+            .filter(|(_, value)| *value == best_value)
+            .map(|(state, _)| state)
+            .collect()
+    }
+}
+
+// canonical representative of a child state, applying the same swap-to-+1 and
+// symmetry-minimisation that `Table` uses on its keys, so a remembered best move can
+// be matched against a freshly generated possibility.
+fn child_key<'a, S: GameState<'a>>(child: &S, player: i32) -> S {
+    let mut child = child.clone();
+    if player == -1 {
+        child.swap();
+    }
+    child.symmetries().into_iter().min().unwrap()
+}
+
+// entry point for the parallel search. The rayon pool is built once, at construction,
+// and reused across every move rather than being spun up per call.
+#[derive(Clone)]
+pub struct ParallelSearch {
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl Default for ParallelSearch {
+    fn default() -> ParallelSearch {
+        ParallelSearch::new()
+    }
+}
+
+impl ParallelSearch {
+    // use rayon's global pool.
+    pub fn new() -> ParallelSearch {
+        ParallelSearch { pool: None }
+    }
+
+    // use a private pool of `n` threads, reused for the life of this `ParallelSearch`.
+    // Propagates the pool-creation error instead of panicking on failure.
+    pub fn with_threads(n: usize) -> Result<ParallelSearch, rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(n).build()?;
+        Ok(ParallelSearch {
+            pool: Some(Arc::new(pool)),
+        })
+    }
+
+    pub fn bot_play<'a, S>(
+        &self,
+        state: &S,
+        player: i32,
+        depth: i32,
+        table: &Table<S>,
+    ) -> Vec<S>
+    where
+        S: GameState<'a> + Send + Sync,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(|| state.bot_play_parallel(player, depth, table)),
+            None => state.bot_play_parallel(player, depth, table),
+        }
+    }
 }
 
 use std::collections::BTreeMap;
@@ -173,8 +580,126 @@ impl std::ops::Add for Interval {
     }
 }
 
+// a stored position: its proven `interval`, the child that produced the best value
+// (kept canonicalised to order the next, deeper iteration) and the generation it was
+// last written at, used by the bounded store to break replacement ties by age.
 #[derive(Clone)]
-pub struct Table<S: Ord>(BTreeMap<(i32, S), Interval>);
+struct Slot<S> {
+    interval: Interval,
+    best: Option<S>,
+    generation: u64,
+}
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// number of independent locks the table is split across so concurrent searches
+// hashing to different shards don't serialize on a single mutex.
+const NUM_SHARDS: usize = 64;
+
+// a single bucket of the bounded store: the stored key and slot, or empty.
+type Bucket<S> = Option<((i32, S), Slot<S>)>;
+
+// backing store of a single shard. `new()` keeps the historical unbounded ordered map;
+// `with_capacity` switches to a fixed-size bucket array, which also drops the per-lookup
+// `BTreeMap` comparison cost.
+#[derive(Clone)]
+enum Store<S: Ord> {
+    Unbounded(BTreeMap<(i32, S), Slot<S>>),
+    Bounded(Vec<Bucket<S>>),
+}
+
+fn bucket_index<'a, S: GameState<'a>>(key: &(i32, S), n: usize) -> usize {
+    let mut h = DefaultHasher::new();
+    key.hash(&mut h);
+    (h.finish() as usize) % n
+}
+
+impl<'a, S> Store<S>
+where
+    S: GameState<'a>,
+{
+    fn len(&self) -> usize {
+        match self {
+            Store::Unbounded(m) => m.len(),
+            Store::Bounded(v) => v.iter().filter(|slot| slot.is_some()).count(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Store::Unbounded(m) => m.is_empty(),
+            Store::Bounded(v) => v.iter().all(|slot| slot.is_none()),
+        }
+    }
+
+    fn get(&self, key: &(i32, S)) -> Option<&Slot<S>> {
+        match self {
+            Store::Unbounded(m) => m.get(key),
+            Store::Bounded(v) => match &v[bucket_index(key, v.len())] {
+                Some((k, slot)) if k == key => Some(slot),
+                _ => None,
+            },
+        }
+    }
+
+    fn insert(&mut self, key: (i32, S), entry: Interval, best: Option<S>, generation: u64) {
+        match self {
+            Store::Unbounded(m) => {
+                let slot = m.entry(key).or_insert(Slot {
+                    interval: Interval::Unconstrained,
+                    best: None,
+                    generation,
+                });
+                slot.interval = slot.interval + entry;
+                if best.is_some() {
+                    slot.best = best;
+                }
+                slot.generation = generation;
+            }
+            Store::Bounded(v) => {
+                let i = bucket_index(&key, v.len());
+                match &mut v[i] {
+                    // same key: merge exactly as the unbounded store does
+                    Some((k, slot)) if *k == key => {
+                        slot.interval = slot.interval + entry;
+                        if best.is_some() {
+                            slot.best = best;
+                        }
+                        slot.generation = generation;
+                    }
+                    // empty or a different key: depth-preferred replacement, keeping the
+                    // entry proved deeper (or, at equal depth, the fresher one)
+                    cell => {
+                        let replace = match cell {
+                            Some((k, slot)) => {
+                                key.0 > k.0 || (key.0 == k.0 && generation > slot.generation)
+                            }
+                            None => true,
+                        };
+                        if replace {
+                            *cell = Some((
+                                key,
+                                Slot {
+                                    interval: entry,
+                                    best,
+                                    generation,
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct Table<S: Ord> {
+    shards: Vec<Mutex<Store<S>>>,
+    generation: AtomicU64,
+}
 
 impl<'a, S> Default for Table<S>
 where
@@ -185,20 +710,68 @@ where
     }
 }
 
+impl<'a, S> Clone for Table<S>
+where
+    S: GameState<'a>,
+{
+    fn clone(&self) -> Table<S> {
+        Table {
+            shards: self
+                .shards
+                .iter()
+                .map(|shard| Mutex::new(shard.lock().unwrap().clone()))
+                .collect(),
+            generation: AtomicU64::new(self.generation.load(Ordering::Relaxed)),
+        }
+    }
+}
+
 impl<'a, S> Table<S>
 where
     S: GameState<'a>,
 {
     pub fn new() -> Table<S> {
-        Table(BTreeMap::new())
+        Table {
+            shards: (0..NUM_SHARDS)
+                .map(|_| Mutex::new(Store::Unbounded(BTreeMap::new())))
+                .collect(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    // a capacity-bounded table: each key maps to a single bucket, and when that bucket
+    // already holds a different key the entry proved at the greater depth (or, at equal
+    // depth, the newer one) is kept, so the expensively-proved deep ones survive. This
+    // replacement happens on any bucket collision, even while other buckets are empty.
+    //
+    // For small `n` the shard count is clamped to `n` so a request like `with_capacity(8)`
+    // does not silently allocate one bucket per shard. The per-shard bucket count rounds
+    // up, so the real capacity is `per_shard * shard_count`: `n` rounded up to a multiple
+    // of the shard count (never below `n`, at most `shard_count - 1` above it). `n == 0`
+    // still allocates a single bucket so `get`/`insert` never divide by an empty store.
+    pub fn with_capacity(n: usize) -> Table<S> {
+        let shard_count = std::cmp::max(1, std::cmp::min(n, NUM_SHARDS));
+        let per_shard = std::cmp::max(1, (n + shard_count - 1) / shard_count);
+        Table {
+            shards: (0..shard_count)
+                .map(|_| Mutex::new(Store::Bounded((0..per_shard).map(|_| None).collect())))
+                .collect(),
+            generation: AtomicU64::new(0),
+        }
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.shards.iter().all(|shard| shard.lock().unwrap().is_empty())
+    }
+
+    fn shard(&self, key: &(i32, S)) -> &Mutex<Store<S>> {
+        let mut h = DefaultHasher::new();
+        key.hash(&mut h);
+        &self.shards[(h.finish() as usize) % self.shards.len()]
     }
 
     pub fn get(
@@ -216,9 +789,12 @@ where
         }
 
         let state = state.symmetries().into_iter().min().unwrap();
+        let key = (depth, state);
+
+        let shard = self.shard(&key).lock().unwrap();
 
-        if let Some(&entry) = self.0.get(&(depth, state)) {
-            match entry {
+        if let Some(slot) = shard.get(&key) {
+            match slot.interval {
                 Interval::Exact(value) => {
                     return Some(value);
                 }
@@ -250,15 +826,35 @@ where
         None
     }
 
+    // the best child remembered for `state` when searched at `depth`, in the same
+    // canonical form `child_key` produces, or `None` if the position is unknown.
+    pub fn best_move(&self, state: &S, player: i32, depth: i32) -> Option<S> {
+        if player == -1 {
+            let mut state = state.clone();
+            state.swap();
+            return self.best_move(&state, 1, depth);
+        }
+
+        let state = state.symmetries().into_iter().min().unwrap();
+        let key = (depth, state);
+
+        let shard = self.shard(&key).lock().unwrap();
+        shard.get(&key).and_then(|slot| slot.best.clone())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn insert(
-        &mut self,
+        &self,
         mut state: S,
         player: i32,
         depth: i32,
         alpha: i32,
         beta: i32,
         value: i32,
+        best: Option<S>,
     ) {
+        let best = best.map(|child| child_key(&child, player));
+
         if player == -1 {
             // allways use the player +1 perspective
             state.swap();
@@ -275,7 +871,8 @@ where
             Interval::Exact(value)
         };
 
-        let old = self.0.entry(key).or_insert(Interval::Unconstrained);
-        *old = *old + entry;
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+        let mut shard = self.shard(&key).lock().unwrap();
+        shard.insert(key, entry, best, generation);
     }
 }
@@ -0,0 +1,241 @@
+//! Monte Carlo Tree Search, an alternative to negamax for games whose branching
+//! factor makes exhaustive alpha-beta hopeless. It relies only on the `GameState`
+//! trait (`possibilities`, `win`, `value`) and, like `bot_play`, returns the root
+//! child(ren) it judges best.
+
+use crate::GameState;
+use std::time::{Duration, Instant};
+
+// how long a search runs for: either a fixed number of iterations or a wall-clock
+// budget.
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+// a node of the search tree: its `state`, whose turn it is (`player`), the running
+// win total in the +1 perspective, the visit count, the children not yet expanded and
+// the ones already expanded (as arena indices).
+struct Node<S> {
+    state: S,
+    player: i32,
+    wins: f64,
+    visits: f64,
+    unexplored: Vec<S>,
+    children: Vec<usize>,
+}
+
+// a tiny xorshift generator: the crate pulls in no `rand` dependency, and a reproducible
+// stream is enough to drive the random playouts.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+// the searcher, configured with the UCB1 exploration constant, the playout depth cap
+// and the seed of the playout generator.
+#[derive(Clone, Copy, Debug)]
+pub struct Mcts {
+    exploration: f64,
+    ply_cap: u32,
+    seed: u64,
+}
+
+impl Default for Mcts {
+    fn default() -> Mcts {
+        Mcts::new()
+    }
+}
+
+impl Mcts {
+    pub fn new() -> Mcts {
+        Mcts {
+            exploration: std::f64::consts::SQRT_2,
+            ply_cap: 128,
+            seed: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn with_exploration(mut self, c: f64) -> Mcts {
+        self.exploration = c;
+        self
+    }
+
+    pub fn with_ply_cap(mut self, cap: u32) -> Mcts {
+        self.ply_cap = cap;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Mcts {
+        self.seed = seed;
+        self
+    }
+
+    // run the search from `root` (where it is `player`'s turn) within `budget` and
+    // return the most visited root child(ren), mirroring `bot_play`.
+    pub fn search<'a, S>(&self, root: &S, player: i32, budget: Budget) -> Vec<S>
+    where
+        S: GameState<'a>,
+    {
+        let mut arena: Vec<Node<S>> = vec![new_node(root.clone(), player)];
+        let mut rng = Rng::new(self.seed);
+
+        match budget {
+            Budget::Iterations(n) => {
+                for _ in 0..n {
+                    self.iterate(&mut arena, &mut rng);
+                }
+            }
+            Budget::Time(limit) => {
+                let start = Instant::now();
+                while start.elapsed() < limit {
+                    self.iterate(&mut arena, &mut rng);
+                }
+            }
+        }
+
+        let root = &arena[0];
+        let best = root
+            .children
+            .iter()
+            .map(|&c| arena[c].visits)
+            .fold(-1.0, f64::max);
+
+        root.children
+            .iter()
+            .filter(|&&c| arena[c].visits == best)
+            .map(|&c| arena[c].state.clone())
+            .collect()
+    }
+
+    // one selection / expansion / simulation / backpropagation round.
+    fn iterate<'a, S>(&self, arena: &mut Vec<Node<S>>, rng: &mut Rng)
+    where
+        S: GameState<'a>,
+    {
+        // selection: descend through fully expanded nodes, maximizing UCB1
+        let mut path = vec![0usize];
+        let mut node = 0usize;
+        while arena[node].unexplored.is_empty() && !arena[node].children.is_empty() {
+            node = self.best_child(arena, node);
+            path.push(node);
+        }
+
+        // expansion: grow one of the remaining children
+        if let Some(state) = arena[node].unexplored.pop() {
+            let player = -arena[node].player;
+            let idx = arena.len();
+            arena.push(new_node(state, player));
+            arena[node].children.push(idx);
+            node = idx;
+            path.push(idx);
+        }
+
+        // simulation: a random playout from the freshly reached node
+        let reward = self.simulate(&arena[node].state, arena[node].player, rng);
+
+        // backpropagation: the reward stays in the +1 perspective; each node's
+        // exploitation term flips its sign according to whose turn it is.
+        for &idx in &path {
+            arena[idx].visits += 1.0;
+            arena[idx].wins += reward;
+        }
+    }
+
+    fn best_child<'a, S>(&self, arena: &[Node<S>], node: usize) -> usize
+    where
+        S: GameState<'a>,
+    {
+        let player = arena[node].player as f64;
+        let parent_visits = arena[node].visits;
+
+        let mut best = arena[node].children[0];
+        let mut best_score = std::f64::NEG_INFINITY;
+        for &child in &arena[node].children {
+            let c = &arena[child];
+            let score = if c.visits == 0.0 {
+                std::f64::INFINITY
+            } else {
+                let mean = player * (c.wins / c.visits);
+                mean + self.exploration * (parent_visits.ln() / c.visits).sqrt()
+            };
+            if score > best_score {
+                best_score = score;
+                best = child;
+            }
+        }
+        best
+    }
+
+    fn simulate<'a, S>(&self, state: &S, player: i32, rng: &mut Rng) -> f64
+    where
+        S: GameState<'a>,
+    {
+        let mut state = state.clone();
+        let mut player = player;
+
+        for _ in 0..self.ply_cap {
+            if state.win(1) {
+                return 1.0;
+            }
+            if state.win(-1) {
+                return -1.0;
+            }
+
+            let moves: Vec<S> = state.possibilities(player).into_iter().collect();
+            if moves.is_empty() {
+                break;
+            }
+
+            let i = rng.below(moves.len());
+            state = moves[i].clone();
+            player = -player;
+        }
+
+        // no decisive result (the ply cap was hit or no move was left): fall back to the
+        // heuristic value
+        match state.value() {
+            v if v > 0 => 1.0,
+            v if v < 0 => -1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+fn new_node<'a, S>(state: S, player: i32) -> Node<S>
+where
+    S: GameState<'a>,
+{
+    let terminal = state.win(1) || state.win(-1);
+    let unexplored = if terminal {
+        Vec::new()
+    } else {
+        state.possibilities(player).into_iter().collect()
+    };
+
+    Node {
+        state,
+        player,
+        wins: 0.0,
+        visits: 0.0,
+        unexplored,
+        children: Vec::new(),
+    }
+}